@@ -5,11 +5,35 @@
 // v. 2.0. If a copy of the MPL was not distributed with this file, You can
 // obtain one at https://mozilla.org/MPL/2.0/.
 
+use core::fmt;
+use core::mem::MaybeUninit;
+
 use crate::{ecall1, ecall3, SbiError};
 
 /// Debug Console Extension (DCE) ID
 pub const EXTENSION_ID: usize = 0x4442434e;
 
+/// A physical address of a debug-console buffer.
+///
+/// The DCE `write`/`read` calls describe the buffer with two XLEN words — the
+/// lower and higher halves of its physical address. On RV32 with Sv32, physical
+/// addresses are 34 bits wide and therefore do not fit in a single XLEN word, so
+/// this type carries the full address independently of the target pointer width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PhysAddr(pub u64);
+
+/// Split a physical address into its lower and higher XLEN halves for `a1`/`a2`.
+#[cfg(target_pointer_width = "64")]
+fn split_phys(addr: u64) -> (usize, usize) {
+    (addr as usize, 0)
+}
+
+/// Split a physical address into its lower and higher XLEN halves for `a1`/`a2`.
+#[cfg(target_pointer_width = "32")]
+fn split_phys(addr: u64) -> (usize, usize) {
+    (addr as usize, (addr >> 32) as usize)
+}
+
 /// Write a single byte to the debug console. This function will block until the
 /// specified byte is written to the debug console.
 /// 
@@ -46,11 +70,62 @@ pub fn write_byte(byte: u8) -> Result<(), SbiError> {
 /// access parameters.
 /// [`SbiError::Failed`]: Write failed due to I/O errors.
 pub fn write(bytes: &[u8]) -> Result<usize, SbiError> {
-    unsafe {
-        ecall3(bytes.len(),
-        (&bytes[0] as *const u8) as usize, 0,
-        EXTENSION_ID, 0)
+    // SAFETY: a `&[u8]` guarantees the whole slice is valid and accessible for
+    // the duration of the call, satisfying `write_phys`'s physical-range contract.
+    unsafe { write_phys(PhysAddr(bytes.as_ptr() as u64), bytes.len()) }
+}
+
+/// Write `len` bytes starting at the physical address `addr` to the debug
+/// console. This is the address-explicit form of [`write`]: the address is split
+/// into its lower XLEN bits (in `a1`) and higher XLEN bits (in `a2`), which on
+/// RV32 lets a buffer backed by high physical memory (e.g. a 34-bit Sv32 address)
+/// be described — something the `&[u8]`-based [`write`] cannot do, since a slice
+/// pointer is only one XLEN word wide. Like [`write`], it does not block and may
+/// write only part of the sequence; upon success it returns the number of bytes
+/// written.
+///
+/// ### Safety
+///
+/// The whole `[addr, addr + len)` physical range must be accessible to the
+/// supervisor-level software and the SBI implementation must be able to access it
+/// using the PMA attributes.
+///
+/// ### Possible errors
+///
+/// [`SbiError::InvalidParam`]: The byte sequence does not meet the required
+/// memory access parameters.
+/// [`SbiError::Failed`]: Write failed due to I/O errors.
+pub unsafe fn write_phys(addr: PhysAddr, len: usize) -> Result<usize, SbiError> {
+    let (lower, higher) = split_phys(addr.0);
+    unsafe { ecall3(len, lower, higher, EXTENSION_ID, 0) }
+}
+
+/// Write the entire `bytes` slice to the debug console, blocking until every
+/// byte has been accepted. Because [`write`] is non-blocking and may consume
+/// only part of the slice (or nothing), this helper repeatedly calls [`write`]
+/// on the unwritten tail until the slice is fully drained.
+///
+/// An empty slice is written as an immediate `Ok(())` without issuing an ecall.
+///
+/// ### Safety
+///
+/// The same requirements as [`write`] apply: the entire `bytes` slice must be
+/// accessible to the supervisor-level software and the SBI implementation must
+/// be able to access it using the PMA attributes.
+///
+/// ### Possible errors
+///
+/// [`SbiError::InvalidParam`]: The byte sequence does not meet the required
+/// memory access parameters.
+/// [`SbiError::Failed`]: Write failed due to I/O errors.
+pub fn write_all(bytes: &[u8]) -> Result<(), SbiError> {
+    let mut remaining = bytes;
+    while !remaining.is_empty() {
+        let written = write(remaining)?;
+        remaining = &remaining[written..];
     }
+
+    Ok(())
 }
 
 /// Read bytes from the debug console into output memory, up to the length of the
@@ -75,9 +150,246 @@ pub fn write(bytes: &[u8]) -> Result<usize, SbiError> {
 /// in `bytes`. However, it makes no guarantees about how the underyling SBI implementation
 /// will execute the call, and does not check for overflows.
 pub fn read(bytes: &mut [u8]) -> Result<usize, SbiError> {
-    unsafe {
-        ecall3(bytes.len(),
-               (&bytes[0] as *const u8) as usize, 0,
-               EXTENSION_ID, 1)
+    // SAFETY: `[u8]` and `[MaybeUninit<u8>]` share the same layout, and every
+    // element of an already-initialized `&mut [u8]` is trivially a valid
+    // `MaybeUninit<u8>`; the reslice returned by `read_uninit` aliases the same
+    // storage for the same lifetime.
+    let uninit = unsafe {
+        core::slice::from_raw_parts_mut(
+            bytes.as_mut_ptr() as *mut MaybeUninit<u8>,
+            bytes.len(),
+        )
+    };
+
+    read_uninit(uninit).map(|s| s.len())
+}
+
+/// Read bytes from the debug console into uninitialized output memory, up to the
+/// length of the `bytes` slice. Behaves exactly like [`read`], but hands the SBI
+/// implementation raw uninitialized storage to fill rather than requiring the
+/// caller to pre-zero the buffer first, which matters for large DMA-able receive
+/// buffers. On success it returns a `&mut [u8]` reslice covering exactly the
+/// bytes the implementation initialized.
+///
+/// ### Safety
+///
+/// The entire `bytes` slice must be accessible to the supervisor-level software
+/// and the SBI implementation must be able to access the entire `bytes` slice
+/// using the PMA attributes.
+///
+/// This function will not request more bytes to be read than there is space
+/// available in `bytes`. However, it makes no guarantees about how the underyling
+/// SBI implementation will execute the call, and does not check for overflows.
+pub fn read_uninit(bytes: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], SbiError> {
+    let len = bytes.len();
+    let base = bytes.as_mut_ptr();
+
+    // SAFETY: `bytes` is a valid `&mut [MaybeUninit<u8>]` of length `len`, so the
+    // whole physical range is accessible for writes, satisfying `read_phys`.
+    let read = unsafe { read_phys(PhysAddr(base as u64), len)? };
+
+    // SAFETY: on success the SBI implementation has initialized the first `read`
+    // bytes at `base`; `read` never exceeds the `len` we passed.
+    Ok(unsafe { core::slice::from_raw_parts_mut(base as *mut u8, read) })
+}
+
+/// Read up to `len` bytes from the debug console into the physical address
+/// `addr`. This is the address-explicit form of [`read`]: the address is split
+/// into its lower XLEN bits (in `a1`) and higher XLEN bits (in `a2`), which on
+/// RV32 lets a receive buffer backed by high physical memory (e.g. a 34-bit Sv32
+/// address) be described. Like [`read`], it does not block and may read no bytes;
+/// upon success it returns the number of bytes read.
+///
+/// ### Safety
+///
+/// The whole `[addr, addr + len)` physical range must be accessible to the
+/// supervisor-level software and the SBI implementation must be able to access it
+/// using the PMA attributes.
+pub unsafe fn read_phys(addr: PhysAddr, len: usize) -> Result<usize, SbiError> {
+    let (lower, higher) = split_phys(addr.0);
+    unsafe { ecall3(len, lower, higher, EXTENSION_ID, 1) }
+}
+
+/// Fill the entire `bytes` slice with bytes read from the debug console,
+/// blocking until it is full. Because [`read`] is non-blocking and may read
+/// fewer bytes than requested (or none), this helper repeatedly calls [`read`]
+/// into the unfilled remainder until no space is left.
+///
+/// An empty slice is satisfied as an immediate `Ok(())` without issuing an ecall.
+///
+/// ### Safety
+///
+/// The same requirements as [`read`] apply: the entire `bytes` slice must be
+/// accessible to the supervisor-level software and the SBI implementation must
+/// be able to access it using the PMA attributes.
+///
+/// ### Possible errors
+///
+/// [`SbiError::InvalidParam`]: The byte sequence does not meet the required
+/// memory access parameters.
+/// [`SbiError::Failed`]: Read failed due to I/O errors.
+pub fn read_exact(bytes: &mut [u8]) -> Result<(), SbiError> {
+    let mut remaining = bytes;
+    while !remaining.is_empty() {
+        let read = read(remaining)?;
+        remaining = &mut remaining[read..];
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+/// A zero-sized [`core::fmt::Write`] adapter over the debug console.
+///
+/// Because [`write`] is non-blocking and may accept only part of the buffer (or
+/// nothing at all), implementing [`core::fmt::Write`] requires draining the
+/// partial writes in a loop. This type does that once, so kernels can reach for
+/// `write!`/`writeln!` against the debug console rather than reimplementing the
+/// drain loop at every call site.
+///
+/// ```ignore
+/// use core::fmt::Write;
+///
+/// let mut console = sbi::debug_console::DebugConsole;
+/// let _ = writeln!(console, "hart {} online", hart_id);
+/// ```
+pub struct DebugConsole;
+
+impl fmt::Write for DebugConsole {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// Length, in bytes, of the on-stack staging buffer used by [`write_from`] and
+/// [`read_into`] to bridge a non-contiguous buffer to the contiguous-slice DCE
+/// ecalls.
+const STAGING_CHUNK_LEN: usize = 256;
+
+/// A source of bytes that can be drained into the debug console.
+///
+/// Modeled on the Rust-for-Linux `io_buffer` abstraction, this decouples the
+/// console transport from a single contiguous `&[u8]`: ring buffers, bounce
+/// buffers, and scatter segments can all be written from by implementing this
+/// trait. See [`write_from`].
+pub trait IoBufferReader {
+    /// The number of bytes still available to be read.
+    fn len(&self) -> usize;
+
+    /// Whether there are no more bytes available to be read.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read `len` bytes out of the buffer into `out`, advancing the buffer past
+    /// them.
+    ///
+    /// ### Safety
+    ///
+    /// `out` must be valid for writes of `len` bytes, and `len` must not exceed
+    /// [`len`](IoBufferReader::len).
+    unsafe fn read_raw(&mut self, out: *mut u8, len: usize);
+}
+
+/// A sink of bytes that can be filled from the debug console.
+///
+/// The writer counterpart to [`IoBufferReader`]; see [`read_into`].
+pub trait IoBufferWriter {
+    /// The number of bytes of free space remaining in the buffer.
+    fn len(&self) -> usize;
+
+    /// Whether there is no free space remaining in the buffer.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write `len` bytes from `input` into the buffer, advancing the buffer past
+    /// the written region.
+    ///
+    /// ### Safety
+    ///
+    /// `input` must be valid for reads of `len` bytes, and `len` must not exceed
+    /// [`len`](IoBufferWriter::len).
+    unsafe fn write_raw(&mut self, input: *const u8, len: usize);
+}
+
+impl IoBufferReader for &[u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    unsafe fn read_raw(&mut self, out: *mut u8, len: usize) {
+        // SAFETY: the caller guarantees `out` is valid for `len` writes and that
+        // `len <= self.len()`, so the source read stays in bounds.
+        unsafe { core::ptr::copy_nonoverlapping(self.as_ptr(), out, len) };
+        *self = &self[len..];
+    }
+}
+
+impl IoBufferWriter for &mut [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    unsafe fn write_raw(&mut self, input: *const u8, len: usize) {
+        // SAFETY: the caller guarantees `input` is valid for `len` reads and that
+        // `len <= self.len()`, so the destination write stays in bounds.
+        unsafe { core::ptr::copy_nonoverlapping(input, self.as_mut_ptr(), len) };
+        let tail = core::mem::take(self);
+        *self = &mut tail[len..];
+    }
+}
+
+/// Drain `reader` in full to the debug console, blocking until every byte has
+/// been accepted. Bytes are staged through a small on-stack buffer and written
+/// with [`write_all`], so any [`IoBufferReader`] — not just a contiguous slice —
+/// can feed the console.
+///
+/// ### Possible errors
+///
+/// [`SbiError::InvalidParam`]: The staged byte sequence does not meet the
+/// required memory access parameters.
+/// [`SbiError::Failed`]: Write failed due to I/O errors.
+pub fn write_from<R: IoBufferReader + ?Sized>(reader: &mut R) -> Result<(), SbiError> {
+    let mut chunk = [MaybeUninit::<u8>::uninit(); STAGING_CHUNK_LEN];
+
+    while !reader.is_empty() {
+        let n = core::cmp::min(reader.len(), chunk.len());
+        // SAFETY: `chunk` is valid for `n <= chunk.len()` writes and `n` does not
+        // exceed the reader's remaining length.
+        unsafe { reader.read_raw(chunk.as_mut_ptr() as *mut u8, n) };
+        // SAFETY: `read_raw` initialized the first `n` bytes of `chunk`.
+        let staged = unsafe { core::slice::from_raw_parts(chunk.as_ptr() as *const u8, n) };
+        write_all(staged)?;
+    }
+
+    Ok(())
+}
+
+/// Fill `writer` from the debug console. Bytes are staged through a small
+/// on-stack buffer read with [`read_uninit`], so any [`IoBufferWriter`] — not
+/// just a contiguous slice — can be filled. Because the underlying [`read`] is
+/// non-blocking, this returns early once the console has no more bytes available,
+/// even if the writer still has free space.
+///
+/// ### Possible errors
+///
+/// [`SbiError::InvalidParam`]: The staged byte sequence does not meet the
+/// required memory access parameters.
+/// [`SbiError::Failed`]: Read failed due to I/O errors.
+pub fn read_into<W: IoBufferWriter + ?Sized>(writer: &mut W) -> Result<(), SbiError> {
+    let mut chunk = [MaybeUninit::<u8>::uninit(); STAGING_CHUNK_LEN];
+
+    while !writer.is_empty() {
+        let n = core::cmp::min(writer.len(), chunk.len());
+        let filled = read_uninit(&mut chunk[..n])?;
+        if filled.is_empty() {
+            break;
+        }
+
+        // SAFETY: `filled` is valid for reads of `filled.len()` bytes, which does
+        // not exceed the writer's remaining free space.
+        unsafe { writer.write_raw(filled.as_ptr(), filled.len()) };
+    }
+
+    Ok(())
+}